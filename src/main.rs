@@ -20,6 +20,14 @@
 //! star x foo.xz bar/
 //! ```
 //!
+//! `-` as the archive path means stdin/stdout, so `star` can be used in a pipeline:
+//!
+//! ```
+//! curl https://example.com/foo.tar.zst | star -f zst x - ./out
+//!
+//! star -f tar c - ./foo | tar-consuming-cmd
+//! ```
+//!
 //! # more
 //!
 //! star --help
@@ -31,12 +39,14 @@ use std::io::Read;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 
+const REPO: &str = "hjiayz/star";
+
 fn main() {
     let ar_arg = Arg::with_name("archive")
         .value_name("FILE_PATH")
         .required(true)
         .index(1)
-        .help("archive file path");
+        .help("archive file path.use - for stdin/stdout.");
     let app = App::new("star")
         .version(crate_version!())
         .author(crate_authors!())
@@ -46,13 +56,30 @@ fn main() {
                 .value_name("FORMAT")
                 .short("f")
                 .help("archive file format")
-                .possible_values(&["xz", "tar", "zst", "zstd", "gzip", "gz", "z", "tgz"]),
+                .possible_values(&[
+                    "xz", "tar", "zst", "zstd", "gzip", "gz", "z", "tgz", "bz2", "bzip2", "zip",
+                ]),
         )
         .arg(
             Arg::with_name("compression_only")
                 .short("c")
                 .help("compression/decompression only.no tar archive."),
         )
+        .arg(
+            Arg::with_name("level")
+                .value_name("LEVEL")
+                .short("l")
+                .long("level")
+                .takes_value(true)
+                .help("compression level.meaning and range depend on the format(default: 9, or 21 for zstd)."),
+        )
+        .arg(
+            Arg::with_name("window")
+                .value_name("WINDOW_MB")
+                .long("window")
+                .takes_value(true)
+                .help("xz dictionary/window size in MiB, up to 1536 (default: whatever the level's preset uses, e.g. 64 at level 9).a bigger window improves the ratio on large archives but raises the memory needed to both compress and decompress."),
+        )
         .subcommand(
             SubCommand::with_name("c")
                 .about("new archive")
@@ -69,7 +96,7 @@ fn main() {
         .subcommand(
             SubCommand::with_name("x")
                 .about("extract archive")
-                .arg(ar_arg)
+                .arg(ar_arg.clone())
                 .arg(
                     Arg::with_name("extract")
                         .value_name("EXTRACT_DIR")
@@ -77,12 +104,39 @@ fn main() {
                         .takes_value(true)
                         .help("extract to the path."),
                 ),
+        )
+        .subcommand(
+            SubCommand::with_name("l")
+                .about("list archive contents")
+                .arg(ar_arg),
+        )
+        .subcommand(
+            SubCommand::with_name("self-update")
+                .about("update star to the latest github release")
+                .arg(
+                    Arg::with_name("pin_version")
+                        .value_name("TAG")
+                        .long("version")
+                        .takes_value(true)
+                        .help("update to a specific release tag instead of the latest"),
+                )
+                .arg(
+                    Arg::with_name("no_confirm")
+                        .long("no-confirm")
+                        .help("update without prompting for confirmation"),
+                ),
         );
     let mut help = Vec::new();
     app.write_help(&mut help).unwrap();
     let matches = app.get_matches();
     let format_type = matches.value_of("format");
     let compression_only = matches.is_present("compression_only");
+    let level = matches
+        .value_of("level")
+        .map(|l| l.parse().expect("level must be a number"));
+    let window = matches
+        .value_of("window")
+        .map(|w| w.parse().expect("window must be a number"));
     if let Some(ref smatches) = matches.subcommand_matches("c") {
         let archive: &Path = smatches.value_of("archive").unwrap().as_ref();
         let append = smatches.values_of("append").unwrap();
@@ -92,7 +146,14 @@ fn main() {
             std::io::stdout().write_all(&help).unwrap();
             return;
         }
-        create(format_type.unwrap(), append, archive, compression_only);
+        create(
+            format_type.unwrap(),
+            append,
+            archive,
+            compression_only,
+            level,
+            window,
+        );
         return;
     }
     if let Some(ref smatches) = matches.subcommand_matches("x") {
@@ -107,13 +168,42 @@ fn main() {
         extract(format_type.unwrap(), archive, dst, compression_only);
         return;
     }
+    if let Some(ref smatches) = matches.subcommand_matches("l") {
+        let archive: &Path = smatches.value_of("archive").unwrap().as_ref();
+        let format_type = check_format_type(format_type, archive);
+        if format_type.is_none() {
+            println!("unknown format");
+            std::io::stdout().write_all(&help).unwrap();
+            return;
+        }
+        list(format_type.unwrap(), archive, compression_only);
+        return;
+    }
+    if let Some(ref smatches) = matches.subcommand_matches("self-update") {
+        let pin_version = smatches.value_of("pin_version");
+        let no_confirm = smatches.is_present("no_confirm");
+        self_update(pin_version, no_confirm);
+        return;
+    }
+}
+
+/// When `quiet` is set the archive itself is being streamed to stdout, so progress
+/// lines must go to stderr instead or they would corrupt the archive bytes.
+fn status_out(quiet: bool) -> Box<dyn Write> {
+    if quiet {
+        Box::new(std::io::stderr())
+    } else {
+        Box::new(std::io::stdout())
+    }
 }
 
 fn append<W: Write>(
     ar: &mut tar::Builder<W>,
     src: Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>,
     target: Option<&Path>,
+    quiet: bool,
 ) {
+    let mut status = status_out(quiet);
     for path in src {
         let path = path.unwrap();
         let mut buf;
@@ -125,15 +215,53 @@ fn append<W: Write>(
         }
         if path.is_dir() {
             ar.append_dir_all(&target, &path).unwrap();
-            println!(
+            writeln!(
+                status,
                 "dir {} to {}",
                 path.to_string_lossy(),
                 target.to_string_lossy()
-            );
+            )
+            .unwrap();
             continue;
         }
 
         ar.append_path_with_name(&path, &target).unwrap();
+        writeln!(
+            status,
+            "file {} to {}",
+            path.to_string_lossy(),
+            target.to_string_lossy()
+        )
+        .unwrap();
+    }
+}
+
+fn append_zip<W: Write + std::io::Seek>(
+    ar: &mut zip::ZipWriter<W>,
+    src: Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>,
+    target: Option<&Path>,
+) {
+    let options = zip::write::FileOptions::default();
+    for path in src {
+        let path = path.unwrap();
+        let mut buf;
+        let mut target = *target.as_ref().unwrap_or(&path.as_ref());
+        if target_is_dir(target) {
+            buf = target.to_path_buf();
+            buf.push(path.file_name().unwrap());
+            target = buf.as_path();
+        }
+        if path.is_dir() {
+            append_zip_dir(ar, &path, target, options);
+            println!(
+                "dir {} to {}",
+                path.to_string_lossy(),
+                target.to_string_lossy()
+            );
+            continue;
+        }
+        ar.start_file(target.to_string_lossy(), options).unwrap();
+        std::io::copy(&mut File::open(&path).unwrap(), ar).unwrap();
         println!(
             "file {} to {}",
             path.to_string_lossy(),
@@ -142,32 +270,205 @@ fn append<W: Write>(
     }
 }
 
-fn check_format_type(format_type: Option<&str>, path: &Path) -> Option<&'static str> {
-    let t = format_type
-        .or_else(|| Some(path.extension()?.to_str()?))?
-        .to_lowercase();
-    Some(match t.as_str() {
+fn append_zip_dir<W: Write + std::io::Seek>(
+    ar: &mut zip::ZipWriter<W>,
+    src: &Path,
+    target: &Path,
+    options: zip::write::FileOptions,
+) {
+    ar.add_directory(target.to_string_lossy(), options).unwrap();
+    for entry in std::fs::read_dir(src).unwrap() {
+        let entry = entry.unwrap();
+        let path = entry.path();
+        let mut child = target.to_path_buf();
+        child.push(path.file_name().unwrap());
+        if path.is_dir() {
+            append_zip_dir(ar, &path, &child, options);
+            continue;
+        }
+        ar.start_file(child.to_string_lossy(), options).unwrap();
+        std::io::copy(&mut File::open(&path).unwrap(), ar).unwrap();
+    }
+}
+
+/// walks the `append` CLI params (`[from ]APPEND_PATH[ to NEW_PATH]`) and invokes
+/// `append_fn` once per glob group, just like `create` does for a tar archive.
+fn for_each_append_group<F>(append_files: clap::Values, mut append_fn: F)
+where
+    F: FnMut(Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>, Option<&Path>),
+{
+    let mut paths: Option<Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>> = None;
+    let mut from = false;
+    let mut to = false;
+    for param in append_files {
+        let lowcase = param.to_lowercase();
+        if lowcase == "from" {
+            from = true;
+            continue;
+        }
+        if to {
+            append_fn(paths.take().unwrap(), Some(param.as_ref()));
+            to = false;
+            continue;
+        }
+        if paths.is_some() {
+            if lowcase == "to" {
+                to = true;
+                from = false;
+                continue;
+            }
+            if from {
+                paths = paths.map(|src| {
+                    Box::new(src.chain(glob::glob(param).unwrap()))
+                        as Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>
+                });
+                continue;
+            }
+            append_fn(paths.take().unwrap(), None);
+            continue;
+        }
+        paths = Some(Box::new(glob::glob(param).unwrap()));
+    }
+    if paths.is_some() {
+        append_fn(paths.take().unwrap(), None);
+    }
+}
+
+fn create_zip(append_files: clap::Values, filepath: &Path) {
+    let file = std::fs::File::create(filepath).unwrap();
+    let mut ar = zip::ZipWriter::new(file);
+    for_each_append_group(append_files, |paths, target| {
+        append_zip(&mut ar, paths, target)
+    });
+    ar.finish().unwrap();
+    println!("{} created.", filepath.to_str().unwrap());
+}
+
+fn extract_zip(filepath: &Path, dst: &str) {
+    let dst: &Path = dst.as_ref();
+    if dst.exists() & (!dst.is_dir()) {
+        panic!("dst path {} exists", dst.display());
+    }
+    let file = std::fs::File::open(filepath).unwrap();
+    let mut ar = zip::ZipArchive::new(file).unwrap();
+    ar.extract(dst).unwrap();
+    println!("ok.")
+}
+
+fn canonical_format(t: &str) -> Option<&'static str> {
+    Some(match t {
         "xz" => "xz",
         "gzip" | "gz" | "tgz" | "z" => "gzip",
         "tar" => "tar",
         "zst" | "zstd" => "zstd",
+        "bz2" | "bzip2" => "bzip2",
+        "zip" => "zip",
         _ => None?,
     })
 }
 
-fn get_encoder(file_type: &str, file: File) -> Box<dyn Write> {
+/// Reads the leading bytes of `path` and matches them against the magic numbers of
+/// the formats `star` understands, falling back to the `ustar` signature at offset
+/// 257 for a plain tar stream. Used when neither `-f` nor the extension tell us the
+/// format, e.g. `star x some-renamed-file`.
+fn sniff_format(path: &Path) -> Option<&'static str> {
+    let mut file = File::open(path).ok()?;
+    let mut buf = [0u8; 512];
+    let n = file.read(&mut buf).ok()?;
+    let buf = &buf[..n];
+    if buf.starts_with(&[0xFD, 0x37, 0x7A, 0x58, 0x5A, 0x00]) {
+        return Some("xz");
+    }
+    if buf.starts_with(&[0x1F, 0x8B]) {
+        return Some("gzip");
+    }
+    if buf.starts_with(&[0x28, 0xB5, 0x2F, 0xFD]) {
+        return Some("zstd");
+    }
+    if buf.starts_with(&[0x42, 0x5A, 0x68]) {
+        return Some("bzip2");
+    }
+    if buf.starts_with(&[0x50, 0x4B, 0x03, 0x04]) {
+        return Some("zip");
+    }
+    if buf.len() >= 262 && &buf[257..262] == b"ustar" {
+        return Some("tar");
+    }
+    None
+}
+
+fn check_format_type(format_type: Option<&str>, path: &Path) -> Option<&'static str> {
+    if let Some(t) = format_type {
+        return canonical_format(&t.to_lowercase());
+    }
+    if is_stdio(path) {
+        // can't seek a pipe to peek its header, so stdin always needs an explicit -f.
+        return None;
+    }
+    if let Some(t) = path.extension().and_then(|e| e.to_str()) {
+        if let Some(t) = canonical_format(&t.to_lowercase()) {
+            return Some(t);
+        }
+    }
+    sniff_format(path)
+}
+
+/// `-` means stdin/stdout, so `star` can be used in a pipeline without a temp file,
+/// e.g. `curl ... | star -f zst x - ./out`.
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn open_input(path: &Path) -> Box<dyn Read> {
+    if is_stdio(path) {
+        return Box::new(std::io::stdin());
+    }
+    Box::new(File::open(path).unwrap())
+}
+
+fn open_output(path: &Path) -> Box<dyn Write> {
+    if is_stdio(path) {
+        return Box::new(std::io::stdout());
+    }
+    Box::new(File::create(path).unwrap())
+}
+
+fn get_encoder(
+    file_type: &str,
+    out: Box<dyn Write>,
+    level: Option<u32>,
+    window: Option<u32>,
+) -> Box<dyn Write> {
     match file_type {
-        "xz" => Box::new(xz2::write::XzEncoder::new(file, 9)),
+        "xz" => {
+            let mut opts =
+                xz2::stream::LzmaOptions::new_preset(level.unwrap_or(9)).expect("invalid xz level");
+            if let Some(mb) = window {
+                if mb > 1536 {
+                    panic!("window is too large (max 1536 MiB)");
+                }
+                opts.dict_size(mb * 1024 * 1024);
+            }
+            let mut filters = xz2::stream::Filters::new();
+            filters.lzma2(&opts);
+            let stream = xz2::stream::Stream::new_stream_encoder(&filters, xz2::stream::Check::Crc64)
+                .expect("faild to create xz stream");
+            Box::new(xz2::write::XzEncoder::new_stream(out, stream))
+        }
         "gzip" => Box::new(flate2::write::GzEncoder::new(
-            file,
-            flate2::Compression::best(),
+            out,
+            flate2::Compression::new(level.unwrap_or(9)),
         )),
-        "tar" => Box::new(file),
+        "tar" => out,
         "zstd" => Box::new(
-            zstd::stream::write::Encoder::new(file, 21)
+            zstd::stream::write::Encoder::new(out, level.unwrap_or(21) as i32)
                 .expect("faild to create zstd encoder")
                 .auto_finish(),
         ),
+        "bzip2" => Box::new(bzip2::write::BzEncoder::new(
+            out,
+            bzip2::Compression::new(level.unwrap_or(9)),
+        )),
         _ => unreachable!("unknown file type"),
     }
 }
@@ -177,13 +478,25 @@ fn create(
     mut append_files: clap::Values,
     filepath: &Path,
     compression_only: bool,
+    level: Option<u32>,
+    window: Option<u32>,
 ) {
-    if filepath.exists() {
+    if !is_stdio(filepath) && filepath.exists() {
         panic!("file path {} exists", filepath.display());
     }
 
-    let file = std::fs::File::create(filepath).unwrap();
-    let mut encoder = get_encoder(format_type, file);
+    if format_type == "zip" {
+        if compression_only {
+            panic!("zip is already a container format. can not compression only");
+        }
+        if is_stdio(filepath) {
+            panic!("zip archives need a seekable file, can not read/write it as a stream");
+        }
+        create_zip(append_files, filepath);
+        return;
+    }
+
+    let mut encoder = get_encoder(format_type, open_output(filepath), level, window);
     if compression_only {
         let param = append_files.next().expect("source file no exists");
         if append_files.next().is_some() {
@@ -199,72 +512,64 @@ fn create(
         .unwrap();
         let _ = std::io::copy(&mut src_file, &mut encoder).unwrap();
         drop(encoder);
-        println!("{} created.", filepath.to_str().unwrap());
+        writeln!(
+            status_out(is_stdio(filepath)),
+            "{} created.",
+            filepath.to_str().unwrap()
+        )
+        .unwrap();
         return;
     }
     let mut ar = tar::Builder::new(encoder);
-    let mut paths: Option<Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>> = None;
-    let mut from = false;
-    let mut to = false;
-    for param in append_files {
-        let lowcase = param.to_lowercase();
-        if lowcase == "from" {
-            from = true;
-            continue;
-        }
-        if to {
-            append(&mut ar, paths.take().unwrap(), Some(param.as_ref()));
-            to = false;
-            continue;
-        }
-        if paths.is_some() {
-            if lowcase == "to" {
-                to = true;
-                from = false;
-                continue;
-            }
-            if from {
-                paths = paths.map(|src| {
-                    Box::new(src.chain(glob::glob(param).unwrap()))
-                        as Box<dyn Iterator<Item = Result<PathBuf, glob::GlobError>>>
-                });
-                continue;
-            }
-            append(&mut ar, paths.take().unwrap(), None);
-            continue;
-        }
-        paths = Some(Box::new(glob::glob(param).unwrap()));
-    }
-    if paths.is_some() {
-        append(&mut ar, paths.take().unwrap(), None);
-    }
+    let quiet = is_stdio(filepath);
+    for_each_append_group(append_files, |paths, target| {
+        append(&mut ar, paths, target, quiet)
+    });
     ar.finish().unwrap();
-    println!("{} created.", filepath.to_str().unwrap());
+    writeln!(
+        status_out(quiet),
+        "{} created.",
+        filepath.to_str().unwrap()
+    )
+    .unwrap();
 }
 
-fn get_decoder(file_type: &str, file: File) -> Box<dyn Read> {
+fn get_decoder(file_type: &str, input: Box<dyn Read>) -> Box<dyn Read> {
     match file_type {
-        "xz" => Box::new(xz2::read::XzDecoder::new(file)),
-        "gzip" => Box::new(flate2::read::GzDecoder::new(file)),
-        "tar" => Box::new(file),
+        "xz" => Box::new(xz2::read::XzDecoder::new(input)),
+        "gzip" => Box::new(flate2::read::GzDecoder::new(input)),
+        "tar" => input,
         "zstd" => {
-            Box::new(zstd::stream::read::Decoder::new(file).expect("faild to create zstd encoder"))
+            Box::new(zstd::stream::read::Decoder::new(input).expect("faild to create zstd decoder"))
         }
+        "bzip2" => Box::new(bzip2::read::BzDecoder::new(input)),
         _ => unreachable!("unknown file type"),
     }
 }
 
 fn extract(format_type: &str, filepath: &Path, dst: &str, compression_only: bool) {
+    if format_type == "zip" {
+        if compression_only {
+            panic!("zip is already a container format. can not compression only");
+        }
+        if is_stdio(filepath) {
+            panic!("zip archives need a seekable file, can not read it as a stream");
+        }
+        extract_zip(filepath, dst);
+        return;
+    }
     let dst: &Path = dst.as_ref();
-    if dst.exists() & (!dst.is_dir()) {
+    if !compression_only && is_stdio(dst) {
+        panic!("extract dir can not be stdout. a tar archive holds more than one file.");
+    }
+    if !is_stdio(dst) && dst.exists() & (!dst.is_dir()) {
         panic!("dst path {} exists", dst.display());
     }
-    let file = std::fs::File::open(filepath).unwrap();
-    let mut decoder = get_decoder(format_type, file);
+    let mut decoder = get_decoder(format_type, open_input(filepath));
     if compression_only {
-        let mut dstfile = File::create(dst).unwrap();
+        let mut dstfile = open_output(dst);
         let _ = std::io::copy(&mut decoder, &mut dstfile).unwrap();
-        println!("ok.");
+        writeln!(status_out(is_stdio(dst)), "ok.").unwrap();
         return;
     }
     let mut ar = tar::Archive::new(decoder);
@@ -272,6 +577,167 @@ fn extract(format_type: &str, filepath: &Path, dst: &str, compression_only: bool
     println!("ok.")
 }
 
+fn list(format_type: &str, filepath: &Path, compression_only: bool) {
+    if compression_only {
+        println!("listing is unavailable for raw-compressed (non-tar) streams.");
+        return;
+    }
+    if format_type == "zip" {
+        let file = std::fs::File::open(filepath).unwrap();
+        let mut ar = zip::ZipArchive::new(file).unwrap();
+        for i in 0..ar.len() {
+            let entry = ar.by_index(i).unwrap();
+            let kind = if entry.is_dir() { "dir" } else { "file" };
+            println!(
+                "{} {} {} {:o}",
+                kind,
+                entry.name(),
+                entry.size(),
+                entry.unix_mode().unwrap_or(0)
+            );
+        }
+        return;
+    }
+    let decoder = get_decoder(format_type, open_input(filepath));
+    let mut ar = tar::Archive::new(decoder);
+    for entry in ar.entries().unwrap() {
+        let entry = entry.unwrap();
+        let kind = if entry.header().entry_type().is_dir() {
+            "dir"
+        } else {
+            "file"
+        };
+        println!(
+            "{} {} {} {:o}",
+            kind,
+            entry.path().unwrap().to_string_lossy(),
+            entry.header().size().unwrap(),
+            entry.header().mode().unwrap()
+        );
+    }
+}
+
+fn sha256_hex(data: &[u8]) -> String {
+    use sha2::Digest;
+    let mut hasher = sha2::Sha256::new();
+    hasher.update(data);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Queries the `star` github releases API, and if `--version` pins a tag or the
+/// latest tag is newer than the running binary, downloads the release asset for
+/// the current target, checks it against the `<asset>.sha256` digest published
+/// alongside it, extracts it with the same `get_decoder`/`tar::Archive` path used
+/// by `extract`, and swaps the running executable for the new one.
+fn self_update(pin_version: Option<&str>, no_confirm: bool) {
+    let release_url = match pin_version {
+        Some(tag) => format!("https://api.github.com/repos/{}/releases/tags/{}", REPO, tag),
+        None => format!("https://api.github.com/repos/{}/releases/latest", REPO),
+    };
+    let release: serde_json::Value = ureq::get(&release_url)
+        .call()
+        .expect("failed to query github releases")
+        .into_json()
+        .expect("failed to parse github releases response");
+    let tag_name = release["tag_name"]
+        .as_str()
+        .expect("release has no tag_name")
+        .to_string();
+    let current_version =
+        semver::Version::parse(crate_version!()).expect("crate version is not valid semver");
+    if pin_version.is_none() {
+        let tag_version = semver::Version::parse(tag_name.trim_start_matches('v'))
+            .unwrap_or_else(|_| panic!("release tag {} is not a valid semver version", tag_name));
+        if tag_version <= current_version {
+            println!("already up to date (v{}).", current_version);
+            return;
+        }
+    }
+    let asset_name = format!(
+        "star-{}-{}.tar.xz",
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    );
+    let find_asset_url = |name: &str| -> String {
+        release["assets"]
+            .as_array()
+            .and_then(|assets| assets.iter().find(|a| a["name"].as_str() == Some(name)))
+            .and_then(|asset| asset["browser_download_url"].as_str())
+            .unwrap_or_else(|| panic!("no release asset named {} in {}", name, tag_name))
+            .to_string()
+    };
+    let download_url = find_asset_url(&asset_name);
+    let checksum_url = find_asset_url(&format!("{}.sha256", asset_name));
+    if !no_confirm {
+        print!("update to {}? [y/N] ", tag_name);
+        std::io::stdout().flush().unwrap();
+        let mut answer = String::new();
+        std::io::stdin().read_line(&mut answer).unwrap();
+        if !answer.trim().eq_ignore_ascii_case("y") {
+            println!("aborted.");
+            return;
+        }
+    }
+    let mut body = Vec::new();
+    ureq::get(&download_url)
+        .call()
+        .expect("failed to download release asset")
+        .into_reader()
+        .read_to_end(&mut body)
+        .unwrap();
+    let expected_sha256 = ureq::get(&checksum_url)
+        .call()
+        .expect("failed to download release checksum")
+        .into_string()
+        .expect("checksum response is not valid text");
+    let expected_sha256 = expected_sha256
+        .split_whitespace()
+        .next()
+        .expect("checksum file is empty")
+        .to_lowercase();
+    let actual_sha256 = sha256_hex(&body);
+    if actual_sha256 != expected_sha256 {
+        panic!(
+            "checksum mismatch for {}: expected {}, got {}. refusing to install.",
+            asset_name, expected_sha256, actual_sha256
+        );
+    }
+    let decoder = get_decoder("xz", Box::new(std::io::Cursor::new(body)));
+    let mut ar = tar::Archive::new(decoder);
+    let extract_dir = std::env::temp_dir().join(format!("star-self-update-{}", std::process::id()));
+    ar.unpack(&extract_dir).unwrap();
+
+    let exe_name = if cfg!(windows) { "star.exe" } else { "star" };
+    let new_exe = extract_dir.join(exe_name);
+    let current_exe = std::env::current_exe().unwrap();
+    if cfg!(windows) {
+        // the running executable can't be overwritten directly on windows, so move
+        // it out of the way first and drop the new binary into its place.
+        let old_exe = current_exe.with_extension("old.exe");
+        let _ = std::fs::remove_file(&old_exe);
+        std::fs::rename(&current_exe, &old_exe).unwrap();
+    }
+    replace_file(&new_exe, &current_exe);
+    let _ = std::fs::remove_dir_all(&extract_dir);
+    println!("updated to {}.", tag_name);
+}
+
+/// `std::fs::rename` fails with `EXDEV` when `src` and `dst` are on different
+/// filesystems, which is the common case here since `src` lives under the OS temp
+/// dir. When that happens, copy `src` into a temp file next to `dst` first (so the
+/// copy lands on `dst`'s filesystem) and rename that into place, so `dst` is never
+/// observed half-written even if the process dies mid-copy.
+fn replace_file(src: &Path, dst: &Path) {
+    if std::fs::rename(src, dst).is_ok() {
+        return;
+    }
+    let dst_dir = dst.parent().expect("dst has no parent directory");
+    let staged = dst_dir.join(format!(".star-update-{}.tmp", std::process::id()));
+    std::fs::copy(src, &staged).expect("failed to stage updated binary");
+    std::fs::rename(&staged, dst).expect("failed to atomically install updated binary");
+    let _ = std::fs::remove_file(src);
+}
+
 fn target_is_dir(path: &Path) -> bool {
     let path = path.to_string_lossy().to_string();
     if path.len() == 0 {